@@ -4,16 +4,38 @@
 // configurable capacity and eviction callbacks.
 
 use std::collections::HashMap;
+use std::future::Future;
 use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+
+use futures::future::{BoxFuture, FutureExt, Shared};
+
+use crate::logger::{timestamp, warn};
 
 /// A node in the doubly-linked list used by the LRU cache.
 struct CacheNode<K, V> {
     key: K,
     value: V,
+    cost: u64,
+    inserted_at: u64,
     prev: Option<usize>,
     next: Option<usize>,
 }
 
+/// A source of values for cache misses, used by `LruCache::get_or_fetch`.
+pub trait Cacher<K, V> {
+    /// Produce the value for `key`, or `None` if it cannot be produced.
+    fn fetch(&mut self, key: &K) -> Option<V>;
+}
+
+/// A type whose values have a natural eviction cost (e.g. byte size),
+/// used by `LruCache::put_costed` to derive a cost automatically.
+pub trait CostOf {
+    /// Returns the cost this value should occupy in a cost-based cache.
+    fn cost_of(&self) -> u64;
+}
+
 /// A generic LRU cache with O(1) lookup, insertion, and eviction.
 ///
 /// Uses a HashMap for key lookups combined with a doubly-linked list
@@ -21,21 +43,55 @@ struct CacheNode<K, V> {
 /// evicted when the cache exceeds its capacity.
 pub struct LruCache<K, V> {
     capacity: usize,
+    max_cost: Option<u64>,
+    total_cost: u64,
+    ttl: u64,
+    expiration_count: u64,
     map: HashMap<K, usize>,
-    nodes: Vec<CacheNode<K, V>>,
+    nodes: Vec<Option<CacheNode<K, V>>>,
+    free: Vec<usize>,
     head: Option<usize>,
     tail: Option<usize>,
     eviction_count: u64,
 }
 
 impl<K: Clone + Eq + Hash, V> LruCache<K, V> {
-    /// Create a new LRU cache with the given capacity.
+    /// Create a new LRU cache with the given entry-count capacity.
     pub fn new(capacity: usize) -> Self {
         assert!(capacity > 0, "LRU cache capacity must be positive");
+        Self::with_capacity_hint(capacity, capacity.min(1024))
+    }
+
+    /// Create a cache that evicts by total entry cost instead of entry
+    /// count. Entries are inserted with `put_with_cost`; the cache evicts
+    /// from the tail until `current_cost() <= max_cost`.
+    pub fn with_cost_capacity(max_cost: u64) -> Self {
+        let mut cache = Self::with_capacity_hint(usize::MAX, 0);
+        cache.max_cost = Some(max_cost);
+        cache
+    }
+
+    /// Create a cache where entries expire `ttl_secs` after insertion,
+    /// regardless of recency. A `ttl_secs` of zero disables expiration.
+    pub fn with_ttl(capacity: usize, ttl_secs: u64) -> Self {
+        let mut cache = Self::new(capacity);
+        cache.ttl = ttl_secs;
+        cache
+    }
+
+    /// Builds an empty cache with the given logical entry-count
+    /// `capacity`, pre-allocating storage for `size_hint` entries up
+    /// front rather than for the (possibly unbounded) `capacity`.
+    fn with_capacity_hint(capacity: usize, size_hint: usize) -> Self {
         LruCache {
             capacity,
-            map: HashMap::with_capacity(capacity),
-            nodes: Vec::with_capacity(capacity),
+            max_cost: None,
+            total_cost: 0,
+            ttl: 0,
+            expiration_count: 0,
+            map: HashMap::with_capacity(size_hint),
+            nodes: Vec::with_capacity(size_hint),
+            free: Vec::new(),
             head: None,
             tail: None,
             eviction_count: 0,
@@ -45,38 +101,59 @@ impl<K: Clone + Eq + Hash, V> LruCache<K, V> {
     /// Get a reference to the value associated with the key.
     /// Marks the entry as most recently used.
     pub fn get(&mut self, key: &K) -> Option<&V> {
-        if let Some(&idx) = self.map.get(key) {
-            self.move_to_front(idx);
-            Some(&self.nodes[idx].value)
-        } else {
-            None
+        let &idx = self.map.get(key)?;
+        if self.is_expired(idx) {
+            self.expire_entry(idx);
+            return None;
         }
+        self.move_to_front(idx);
+        Some(&self.node(idx).value)
     }
 
     /// Insert a key-value pair into the cache.
     /// If the key already exists, updates the value.
     /// If the cache is full, evicts the least recently used entry.
     pub fn put(&mut self, key: K, value: V) {
+        self.put_with_cost(key, value, 1);
+    }
+
+    /// Insert a key-value pair with an explicit eviction cost.
+    ///
+    /// In cost-based mode (see `with_cost_capacity`), entries are evicted
+    /// from the tail until `current_cost() <= max_cost()`. If a single
+    /// entry's cost exceeds `max_cost`, every other entry is evicted and
+    /// the oversized entry is stored alone rather than rejected.
+    /// Updating an existing key adjusts `total_cost` by the delta.
+    pub fn put_with_cost(&mut self, key: K, value: V, cost: u64) {
         if let Some(&idx) = self.map.get(&key) {
-            self.nodes[idx].value = value;
+            let old_cost = self.node(idx).cost;
+            let node = self.node_mut(idx);
+            node.value = value;
+            node.cost = cost;
+            node.inserted_at = timestamp();
+            self.total_cost = self.total_cost - old_cost + cost;
             self.move_to_front(idx);
+            self.evict_lru();
             return;
         }
 
-        if self.nodes.len() >= self.capacity {
-            self.evict_lru();
+        if self.max_cost.is_some_and(|max_cost| cost > max_cost) {
+            while self.tail.is_some() {
+                self.evict_one();
+            }
         }
 
-        let idx = self.nodes.len();
-        self.nodes.push(CacheNode {
+        let idx = self.alloc_slot(CacheNode {
             key: key.clone(),
             value,
+            cost,
+            inserted_at: timestamp(),
             prev: None,
             next: self.head,
         });
 
         if let Some(old_head) = self.head {
-            self.nodes[old_head].prev = Some(idx);
+            self.node_mut(old_head).prev = Some(idx);
         }
         self.head = Some(idx);
         if self.tail.is_none() {
@@ -84,16 +161,27 @@ impl<K: Clone + Eq + Hash, V> LruCache<K, V> {
         }
 
         self.map.insert(key, idx);
+        self.total_cost += cost;
+        self.evict_lru();
     }
 
-    /// Returns the number of entries in the cache.
+    /// Returns the number of entries in the cache, excluding any that
+    /// have expired but have not yet been swept by `get`/`peek`/
+    /// `purge_expired`. With a TTL enabled this is O(n).
     pub fn len(&self) -> usize {
-        self.map.len()
+        if self.ttl == 0 {
+            return self.map.len();
+        }
+        let now = timestamp();
+        self.map
+            .values()
+            .filter(|&&idx| now.saturating_sub(self.node(idx).inserted_at) <= self.ttl)
+            .count()
     }
 
-    /// Returns true if the cache is empty.
+    /// Returns true if the cache has no unexpired entries.
     pub fn is_empty(&self) -> bool {
-        self.map.is_empty()
+        self.len() == 0
     }
 
     /// Returns the total number of evictions since creation.
@@ -101,41 +189,415 @@ impl<K: Clone + Eq + Hash, V> LruCache<K, V> {
         self.eviction_count
     }
 
+    /// Returns the total number of entries that have lazily expired
+    /// since creation, whether found by `get`/`peek` or `purge_expired`.
+    pub fn expiration_count(&self) -> u64 {
+        self.expiration_count
+    }
+
+    /// Returns the sum of the costs of all entries currently cached.
+    pub fn current_cost(&self) -> u64 {
+        self.total_cost
+    }
+
+    /// Returns the cost budget for cost-based eviction, if enabled.
+    pub fn max_cost(&self) -> Option<u64> {
+        self.max_cost
+    }
+
+    /// Get the value for `key`, falling back to `cacher` on a miss.
+    ///
+    /// On a miss, `cacher.fetch(&key)` is called; if it returns `Some`,
+    /// the result is inserted (respecting eviction) and a reference to
+    /// the newly cached value is returned. A `None` from `fetch` leaves
+    /// the cache untouched and is propagated to the caller.
+    pub fn get_or_fetch<C: Cacher<K, V>>(&mut self, key: K, cacher: &mut C) -> Option<&V> {
+        if self.get(&key).is_some() {
+            return self.get(&key);
+        }
+
+        let value = cacher.fetch(&key)?;
+        self.put(key.clone(), value);
+        self.get(&key)
+    }
+
+    /// Get the value for `key`, computing and inserting it via `f` on a miss.
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce() -> V) -> &V {
+        if self.get(&key).is_none() {
+            let value = f();
+            self.put(key.clone(), value);
+        }
+        self.get(&key).expect("just inserted")
+    }
+
+    /// Read the value for `key` without affecting its recency. Still
+    /// lazily expires and removes the entry if it is past its TTL.
+    pub fn peek(&mut self, key: &K) -> Option<&V> {
+        let &idx = self.map.get(key)?;
+        if self.is_expired(idx) {
+            self.expire_entry(idx);
+            return None;
+        }
+        Some(&self.node(idx).value)
+    }
+
+    /// Mark an existing entry as most recently used, without returning it.
+    pub fn promote(&mut self, key: &K) {
+        if let Some(&idx) = self.map.get(key) {
+            self.move_to_front(idx);
+        }
+    }
+
+    /// Mark an existing entry as least recently used, so it is the next
+    /// entry evicted, without returning it.
+    pub fn demote(&mut self, key: &K) {
+        if let Some(&idx) = self.map.get(key) {
+            self.move_to_back(idx);
+        }
+    }
+
+    /// Detach and return the key and value of the least recently used
+    /// entry, or `None` if the cache is empty.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let tail_idx = self.tail?;
+        self.detach(tail_idx);
+        let node = self.take_node(tail_idx);
+        self.map.remove(&node.key);
+        self.total_cost -= node.cost;
+        self.free.push(tail_idx);
+        Some((node.key, node.value))
+    }
+
+    /// Removes an entry and returns its value, or `None` if it was not
+    /// present. The freed slot is reclaimed by a later `put`.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let idx = self.map.remove(key)?;
+        self.detach(idx);
+        let node = self.take_node(idx);
+        self.total_cost -= node.cost;
+        self.free.push(idx);
+        Some(node.value)
+    }
+
+    /// Removes every entry, resetting the cache to empty.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.nodes.clear();
+        self.free.clear();
+        self.head = None;
+        self.tail = None;
+        self.total_cost = 0;
+    }
+
+    /// Sweeps every expired entry in one pass. A no-op when TTL is
+    /// disabled. When it removes any entries, logs a warning naming how
+    /// many; a sweep that finds nothing stale logs nothing.
+    pub fn purge_expired(&mut self) {
+        if self.ttl == 0 {
+            return;
+        }
+
+        let now = timestamp();
+        let stale: Vec<usize> = self
+            .map
+            .values()
+            .copied()
+            .filter(|&idx| now.saturating_sub(self.node(idx).inserted_at) > self.ttl)
+            .collect();
+
+        if stale.is_empty() {
+            return;
+        }
+
+        for idx in &stale {
+            self.expire_entry(*idx);
+        }
+
+        warn(&format!("purge_expired evicted {} expired entries", stale.len()));
+    }
+
+    fn is_expired(&self, idx: usize) -> bool {
+        self.ttl != 0 && timestamp().saturating_sub(self.node(idx).inserted_at) > self.ttl
+    }
+
+    fn expire_entry(&mut self, idx: usize) {
+        self.detach(idx);
+        let node = self.take_node(idx);
+        self.map.remove(&node.key);
+        self.total_cost -= node.cost;
+        self.free.push(idx);
+        self.expiration_count += 1;
+    }
+
+    fn node(&self, idx: usize) -> &CacheNode<K, V> {
+        self.nodes[idx].as_ref().expect("map pointed to a freed slot")
+    }
+
+    fn node_mut(&mut self, idx: usize) -> &mut CacheNode<K, V> {
+        self.nodes[idx].as_mut().expect("map pointed to a freed slot")
+    }
+
+    fn take_node(&mut self, idx: usize) -> CacheNode<K, V> {
+        self.nodes[idx].take().expect("map pointed to a freed slot")
+    }
+
+    /// Allocates a slot for `node`, reusing a freed index when one is
+    /// available instead of growing `nodes` unboundedly.
+    fn alloc_slot(&mut self, node: CacheNode<K, V>) -> usize {
+        if let Some(idx) = self.free.pop() {
+            self.nodes[idx] = Some(node);
+            idx
+        } else {
+            let idx = self.nodes.len();
+            self.nodes.push(Some(node));
+            idx
+        }
+    }
+
     fn move_to_front(&mut self, idx: usize) {
         if self.head == Some(idx) {
             return;
         }
         self.detach(idx);
-        self.nodes[idx].prev = None;
-        self.nodes[idx].next = self.head;
+        self.node_mut(idx).prev = None;
+        self.node_mut(idx).next = self.head;
         if let Some(old_head) = self.head {
-            self.nodes[old_head].prev = Some(idx);
+            self.node_mut(old_head).prev = Some(idx);
         }
         self.head = Some(idx);
     }
 
+    fn move_to_back(&mut self, idx: usize) {
+        if self.tail == Some(idx) {
+            return;
+        }
+        self.detach(idx);
+        self.node_mut(idx).next = None;
+        self.node_mut(idx).prev = self.tail;
+        if let Some(old_tail) = self.tail {
+            self.node_mut(old_tail).next = Some(idx);
+        }
+        self.tail = Some(idx);
+        if self.head.is_none() {
+            self.head = Some(idx);
+        }
+    }
+
     fn detach(&mut self, idx: usize) {
-        let prev = self.nodes[idx].prev;
-        let next = self.nodes[idx].next;
+        let prev = self.node(idx).prev;
+        let next = self.node(idx).next;
         if let Some(p) = prev {
-            self.nodes[p].next = next;
+            self.node_mut(p).next = next;
         }
         if let Some(n) = next {
-            self.nodes[n].prev = prev;
+            self.node_mut(n).prev = prev;
+        }
+        if self.head == Some(idx) {
+            self.head = next;
         }
         if self.tail == Some(idx) {
             self.tail = prev;
         }
     }
 
+    /// Evicts tail entries while the cache is over its entry-count
+    /// capacity or, in cost-based mode, its total cost exceeds
+    /// `max_cost`. Always leaves at least one entry so a single
+    /// oversized item can still be cached alone.
     fn evict_lru(&mut self) {
+        while self.map.len() > 1
+            && (self.map.len() > self.capacity
+                || self.max_cost.is_some_and(|max_cost| self.total_cost > max_cost))
+        {
+            self.evict_one();
+        }
+    }
+
+    /// Unconditionally detaches and removes the tail entry, if any,
+    /// pushing its slot onto the free list for reuse.
+    fn evict_one(&mut self) {
         if let Some(tail_idx) = self.tail {
-            let key = self.nodes[tail_idx].key.clone();
             self.detach(tail_idx);
-            self.map.remove(&key);
+            let node = self.take_node(tail_idx);
+            self.map.remove(&node.key);
+            self.total_cost -= node.cost;
             self.eviction_count += 1;
+            self.free.push(tail_idx);
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: CostOf> LruCache<K, V> {
+    /// Insert a key-value pair, deriving its cost from `CostOf::cost_of`.
+    pub fn put_costed(&mut self, key: K, value: V) {
+        let cost = value.cost_of();
+        self.put_with_cost(key, value, cost);
+    }
+}
+
+/// A fixed-capacity LRU cache that permits cache hits from `&self` across
+/// threads, without a global write lock.
+///
+/// Values live in a fixed `Vec<(AtomicU64, T)>`; each cell's `AtomicU64`
+/// records the generation at which it was last accessed. A hit only bumps
+/// the shared `generation` counter and stores it into the cell's atomic
+/// with `Release` ordering, so it never takes a mutable borrow of `self`.
+/// Recency is therefore approximate under heavy contention (two
+/// concurrent hits may interleave generations), but a hit never corrupts
+/// memory. `put` still needs `&mut self`, since choosing and overwriting
+/// an eviction slot is not lock-free; only the key-to-slot map is
+/// additionally guarded by an internal `RwLock` so concurrent `get`s never
+/// block each other.
+pub struct ConcurrentLru<K, T> {
+    capacity: usize,
+    generation: AtomicU64,
+    cells: Vec<(AtomicU64, T)>,
+    map: RwLock<HashMap<K, usize>>,
+}
+
+impl<K: Clone + Eq + Hash, T: Clone> ConcurrentLru<K, T> {
+    /// Create a cache with `capacity` slots, all initially filled with a
+    /// clone of `filler` and untouched (generation 0).
+    pub fn new(capacity: usize, filler: T) -> Self {
+        assert!(capacity > 0, "ConcurrentLru capacity must be positive");
+        let cells = (0..capacity)
+            .map(|_| (AtomicU64::new(0), filler.clone()))
+            .collect();
+        ConcurrentLru {
+            capacity,
+            generation: AtomicU64::new(0),
+            cells,
+            map: RwLock::new(HashMap::with_capacity(capacity)),
+        }
+    }
+
+    /// Returns the value for `key` and records a hit, or `None` on a miss.
+    /// Safe to call concurrently from many threads.
+    pub fn get(&self, key: &K) -> Option<&T> {
+        let idx = *self.map.read().unwrap().get(key)?;
+        self.touch(idx);
+        Some(&self.cells[idx].1)
+    }
+
+    /// Insert or update the value for `key`, evicting the slot with the
+    /// lowest recorded generation when the cache is full.
+    pub fn put(&mut self, key: K, value: T) {
+        let mut map = self.map.write().unwrap();
+        if let Some(&idx) = map.get(&key) {
+            self.cells[idx].1 = value;
+            self.touch(idx);
+            return;
+        }
+
+        let idx = if map.len() < self.capacity {
+            map.len()
+        } else {
+            let idx = self.least_recently_used_slot();
+            if let Some(evicted_key) = map
+                .iter()
+                .find(|&(_, &slot)| slot == idx)
+                .map(|(k, _)| k.clone())
+            {
+                map.remove(&evicted_key);
+            }
+            idx
+        };
+
+        self.cells[idx].1 = value;
+        self.touch(idx);
+        map.insert(key, idx);
+    }
+
+    /// Returns the number of occupied slots.
+    pub fn len(&self) -> usize {
+        self.map.read().unwrap().len()
+    }
+
+    /// Returns true if no slot is occupied.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn touch(&self, idx: usize) {
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        self.cells[idx].0.store(generation, Ordering::Release);
+    }
+
+    fn least_recently_used_slot(&self) -> usize {
+        self.cells
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (generation, _))| generation.load(Ordering::Acquire))
+            .map(|(idx, _)| idx)
+            .expect("capacity is always > 0")
+    }
+}
+
+type PendingFetch<V, E> = Shared<BoxFuture<'static, Result<V, E>>>;
+
+/// An async read-through wrapper over `LruCache` with single-flight
+/// deduplication: when several tasks request the same missing key at
+/// once, only the first calls `fetch`; the rest await its shared result
+/// instead of launching duplicate work. On a fetch error every waiter
+/// observes it and the pending slot is cleared so the next call retries.
+pub struct AsyncLruCache<K, V, E> {
+    cache: Mutex<LruCache<K, V>>,
+    pending: Mutex<HashMap<K, PendingFetch<V, E>>>,
+}
+
+impl<K, V, E> AsyncLruCache<K, V, E>
+where
+    K: Clone + Eq + Hash + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+    E: Clone + Send + Sync + 'static,
+{
+    /// Create an async cache backed by an `LruCache` of the given capacity.
+    pub fn new(capacity: usize) -> Self {
+        AsyncLruCache {
+            cache: Mutex::new(LruCache::new(capacity)),
+            pending: Mutex::new(HashMap::new()),
         }
     }
+
+    /// Get the value for `key`, calling `fetch(key)` on a miss.
+    pub async fn get_or_fetch<F, Fut>(&self, key: K, fetch: F) -> Result<V, E>
+    where
+        F: FnOnce(K) -> Fut,
+        Fut: Future<Output = Result<V, E>> + Send + 'static,
+    {
+        if let Some(value) = self.cache.lock().unwrap().get(&key) {
+            return Ok(value.clone());
+        }
+
+        let shared = {
+            let mut pending = self.pending.lock().unwrap();
+            match pending.get(&key) {
+                Some(shared) => shared.clone(),
+                None => {
+                    let shared = fetch(key.clone()).boxed().shared();
+                    pending.insert(key.clone(), shared.clone());
+                    shared
+                }
+            }
+        };
+
+        let result = shared.clone().await;
+
+        // Whichever waiter observes the pending slot still pointing at
+        // this exact future clears it, so a later call retries instead
+        // of replaying a cached error forever.
+        let mut pending = self.pending.lock().unwrap();
+        if pending.get(&key).is_some_and(|f| f.ptr_eq(&shared)) {
+            pending.remove(&key);
+        }
+        drop(pending);
+
+        if let Ok(value) = &result {
+            self.cache.lock().unwrap().put(key, value.clone());
+        }
+
+        result
+    }
 }
 
 #[cfg(test)]
@@ -151,4 +613,181 @@ mod tests {
         assert_eq!(cache.get(&"alpha"), Some(&1));
         assert_eq!(cache.len(), 3);
     }
+
+    struct StaticCacher(Option<i32>);
+
+    impl Cacher<&'static str, i32> for StaticCacher {
+        fn fetch(&mut self, _key: &&'static str) -> Option<i32> {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_get_or_fetch() {
+        let mut cache = LruCache::new(2);
+        let mut cacher = StaticCacher(Some(42));
+        assert_eq!(cache.get_or_fetch("delta", &mut cacher), Some(&42));
+        assert_eq!(cache.len(), 1);
+
+        let mut empty_cacher = StaticCacher(None);
+        assert_eq!(cache.get_or_fetch("missing", &mut empty_cacher), None);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let mut cache = LruCache::new(2);
+        assert_eq!(*cache.get_or_insert_with("epsilon", || 7), 7);
+        assert_eq!(cache.get(&"epsilon"), Some(&7));
+    }
+
+    #[test]
+    fn test_cost_based_eviction() {
+        let mut cache = LruCache::with_cost_capacity(10);
+        cache.put_with_cost("a", 1, 4);
+        cache.put_with_cost("b", 2, 4);
+        assert_eq!(cache.current_cost(), 8);
+
+        cache.put_with_cost("c", 3, 4);
+        assert_eq!(cache.current_cost(), 8);
+        assert_eq!(cache.get(&"a"), None);
+        assert_eq!(cache.get(&"b"), Some(&2));
+        assert_eq!(cache.get(&"c"), Some(&3));
+    }
+
+    #[test]
+    fn test_oversized_entry_stored_alone() {
+        let mut cache = LruCache::with_cost_capacity(10);
+        cache.put_with_cost("a", 1, 4);
+        cache.put_with_cost("huge", 2, 20);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&"huge"), Some(&2));
+        assert_eq!(cache.current_cost(), 20);
+    }
+
+    #[test]
+    fn test_peek_does_not_affect_order() {
+        let mut cache = LruCache::new(2);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        assert_eq!(cache.peek(&"a"), Some(&1));
+        cache.put("c", 3);
+        assert_eq!(cache.get(&"a"), None);
+    }
+
+    #[test]
+    fn test_promote_and_demote() {
+        let mut cache = LruCache::new(2);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.promote(&"a");
+        cache.put("c", 3);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"a"), Some(&1));
+
+        cache.demote(&"a");
+        cache.put("d", 4);
+        assert_eq!(cache.get(&"a"), None);
+    }
+
+    #[test]
+    fn test_pop_lru() {
+        let mut cache = LruCache::new(3);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.put("c", 3);
+        assert_eq!(cache.pop_lru(), Some(("a", 1)));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&"a"), None);
+    }
+
+    #[test]
+    fn test_remove_reclaims_slot() {
+        let mut cache = LruCache::new(2);
+        cache.put("a", 1);
+        assert_eq!(cache.remove(&"a"), Some(1));
+        assert_eq!(cache.remove(&"a"), None);
+        assert_eq!(cache.len(), 0);
+
+        cache.put("b", 2);
+        cache.put("c", 3);
+        assert_eq!(cache.nodes.len(), 2);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut cache = LruCache::new(3);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.current_cost(), 0);
+        assert_eq!(cache.get(&"a"), None);
+
+        cache.put("c", 3);
+        assert_eq!(cache.get(&"c"), Some(&3));
+    }
+
+    #[test]
+    fn test_concurrent_lru_basic() {
+        let mut cache = ConcurrentLru::new(2, 0);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_ttl_expires_entries() {
+        let mut cache = LruCache::with_ttl(2, 1);
+        cache.put("a", 1);
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        assert_eq!(cache.get(&"a"), None);
+        assert_eq!(cache.expiration_count(), 1);
+    }
+
+    #[test]
+    fn test_ttl_zero_disables_expiration() {
+        let mut cache = LruCache::with_ttl(2, 0);
+        cache.put("a", 1);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert_eq!(cache.get(&"a"), Some(&1));
+    }
+
+    #[test]
+    fn test_purge_expired_sweeps_stale_entries() {
+        let mut cache = LruCache::with_ttl(3, 1);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        cache.put("c", 3);
+        cache.purge_expired();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&"c"), Some(&3));
+    }
+
+    #[test]
+    fn test_async_lru_cache_hit_skips_fetch() {
+        let cache: AsyncLruCache<&str, i32, &str> = AsyncLruCache::new(2);
+
+        let result = futures::executor::block_on(cache.get_or_fetch("a", |_| async { Ok(7) }));
+        assert_eq!(result, Ok(7));
+
+        let result = futures::executor::block_on(
+            cache.get_or_fetch("a", |_| async { panic!("cached key should not refetch") }),
+        );
+        assert_eq!(result, Ok(7));
+    }
+
+    #[test]
+    fn test_concurrent_lru_evicts_least_recently_used() {
+        let mut cache = ConcurrentLru::new(2, 0);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.get(&"a");
+        cache.put("c", 3);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.get(&"c"), Some(&3));
+    }
 }
@@ -12,7 +12,7 @@ pub fn error(message: &str) {
     eprintln!("[ERROR] {} {}", timestamp(), message);
 }
 
-fn timestamp() -> u64 {
+pub fn timestamp() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .expect("time went backwards")